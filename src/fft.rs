@@ -1,7 +1,10 @@
 //! Analyze discrete signal in frequency domain
 
+use std::f64::consts::PI;
+
 use rustfft::{FFT};
-use signals::{Signal};
+use num_complex::{Complex, Complex64};
+use signals::{Signal, Window};
 use spectrums::{Spectrum};
 
 
@@ -11,6 +14,8 @@ pub struct ForwardFFT {
 
 pub struct InverseFFT {
     fft: FFT<f64>,
+    sample_size: usize,
+    normalize: bool,
 }
 
 impl ForwardFFT {
@@ -31,26 +36,176 @@ impl ForwardFFT {
         self.fft.process(&raw_vec, &mut out);
         Spectrum::new(out, v.sample_rate)
     }
+
+    /// Forward DFT of a windowed signal, to reduce spectral leakage
+    pub fn process_windowed(&mut self, v: &Signal, window: Window) -> Spectrum {
+        self.process(&v.apply_window(window))
+    }
+
+    /// Forward DFT of a real-valued signal. Packs consecutive sample pairs
+    /// into one complex half-length FFT, then unscrambles the result via
+    /// Hermitian symmetry into the N/2+1 unique bins. This is half the FFT
+    /// size (and half the memory) of running the full complex transform.
+    pub fn process_real(&mut self, v: &Signal) -> Spectrum {
+        let n = v.len();
+        assert!(n % 2 == 0, "ForwardFFT::process_real requires an even-length signal");
+        let half_n = n / 2;
+
+        let packed: Vec<Complex64> = (0..half_n)
+            .map(|i| Complex::new(v.get(2 * i as isize).re, v.get(2 * i as isize + 1).re))
+            .collect();
+        let mut z = packed.clone();
+        FFT::new(half_n, false).process(&packed, &mut z);
+
+        let bins: Vec<Complex64> = (0..=half_n)
+            .map(|k| {
+                let zk = z[k % half_n];
+                let zc = z[(half_n - k) % half_n].conj();
+                let twiddle = Complex::new(0., -PI * k as f64 / half_n as f64).exp();
+                (zk + zc) * 0.5 - Complex::new(0., 0.5) * twiddle * (zk - zc)
+            })
+            .collect();
+
+        Spectrum::new_real(bins, v.sample_rate)
+    }
 }
 
 
 impl InverseFFT {
-    /// Define new transformation
+    /// Define new transformation, normalized by 1/N so that a forward-then-inverse
+    /// transform reproduces the original signal
     /// ## Params:
     ///   * sample_size - Size of the vector which will be converter. Should be power of 2 (or 3)
     pub fn new(sample_size: usize) -> InverseFFT {
+        InverseFFT::with_normalization(sample_size, true)
+    }
+
+    /// Define new transformation with explicit control over the 1/N scaling
+    /// ## Params:
+    ///   * sample_size - Size of the vector which will be converter. Should be power of 2 (or 3)
+    ///   * normalize - Whether to divide the output by sample_size
+    pub fn with_normalization(sample_size: usize, normalize: bool) -> InverseFFT {
         let fft = FFT::new(sample_size, true);
-        InverseFFT{ fft }
+        InverseFFT { fft, sample_size, normalize }
     }
 
-    /// Forward DFT (implemented as FFT)
+    /// Inverse DFT (implemented as FFT)
     pub fn process(&mut self, v: &Spectrum) -> Signal {
         let raw_vec = v.to_vec();
         let mut out = raw_vec.clone();
 
         self.fft.process(&raw_vec, &mut out);
+        if self.normalize {
+            let n = self.sample_size as f64;
+            for x in out.iter_mut() {
+                *x = *x / n;
+            }
+        }
         Signal::new(out)
     }
+
+    /// Inverse of `ForwardFFT::process_real`: reconstructs the packed
+    /// half-length complex spectrum from the N/2+1 one-sided bins, runs a
+    /// single half-length inverse FFT, then unpacks the result back into a
+    /// purely real signal of length N. With normalization the output is
+    /// divided by N/2, the length of the half-size transform; without it the
+    /// output is N/2 times the original signal. `sample_size` is not used here:
+    /// N is taken from the number of bins.
+    pub fn process_real(&mut self, v: &Spectrum) -> Signal {
+        let x = v.to_vec();
+        let half_n = x.len() - 1;
+        let n = half_n * 2;
+
+        let z: Vec<Complex64> = (0..half_n)
+            .map(|k| {
+                let xk = x[k];
+                let xc = x[half_n - k].conj();
+                let twiddle = Complex::new(0., -PI * k as f64 / half_n as f64).exp();
+                (xk + xc + Complex::new(0., 1.) * twiddle.conj() * (xk - xc)) * 0.5
+            })
+            .collect();
+
+        let mut packed = z.clone();
+        FFT::new(half_n, true).process(&z, &mut packed);
+
+        let scale = if self.normalize { 1. / half_n as f64 } else { 1. };
+        let mut data: Vec<Complex64> = Vec::with_capacity(n);
+        for p in packed.iter() {
+            data.push(Complex::new(p.re * scale, 0.));
+            data.push(Complex::new(p.im * scale, 0.));
+        }
+        Signal::new(data)
+    }
+}
+
+
+/// Estimates the power spectral density of a signal using Welch's method:
+/// the signal is split into overlapping, windowed segments, each is
+/// transformed with one FFT of the segment length, and the resulting periodograms are
+/// averaged bin-by-bin to reduce variance versus a single raw spectrum.
+pub struct Periodogram {
+    segment_len: usize,
+    overlap: f64,
+}
+
+impl Periodogram {
+    /// New estimator with the default 50% segment overlap
+    /// ## Params:
+    ///   * segment_len - Length of each segment. Should be power of 2 (or 3)
+    pub fn new(segment_len: usize) -> Periodogram {
+        Periodogram { segment_len, overlap: 0.5 }
+    }
+
+    /// New estimator with a custom fractional segment overlap (0.0 .. 1.0)
+    pub fn with_overlap(segment_len: usize, overlap: f64) -> Periodogram {
+        Periodogram { segment_len, overlap }
+    }
+
+    /// Estimate the one-sided power spectral density via Welch's method.
+    /// The returned spectrum's `sample_rate` field holds the frequency
+    /// resolution, `signal.sample_rate / segment_len` (integer division),
+    /// not the input's sample rate.
+    pub fn welch_psd(&self, signal: &Signal) -> Spectrum {
+        let l = self.segment_len;
+        let hop = (l as f64 * (1. - self.overlap)) as usize;
+        assert!(hop >= 1, "Periodogram: overlap too large, segments would never advance");
+        assert!(signal.len() >= l, "Periodogram: signal is shorter than segment_len");
+        let half = l / 2 + 1;
+
+        let window_taps = Window::Hann.taps(l);
+        let window_power: f64 = window_taps.iter().map(|w| w * w).sum();
+
+        let mut fft = ForwardFFT::new(l);
+        let mut acc = vec![0.; half];
+        let mut segments = 0;
+
+        let mut start: isize = 0;
+        while start + l as isize <= signal.len() as isize {
+            let data: Vec<Complex64> = (0..l)
+                .map(|n| signal.get(start + n as isize))
+                .collect();
+            let spectrum = fft.process_windowed(&Signal::new(data), Window::Hann);
+            let bins = spectrum.to_vec();
+            for k in 0..half {
+                acc[k] += (bins[k] * bins[k].conj()).re;
+            }
+            segments += 1;
+            start += hop as isize;
+        }
+
+        let scale = 1. / (segments as f64 * window_power * signal.sample_rate as f64);
+        let psd: Vec<Complex64> = (0..half)
+            .map(|k| {
+                let mut p = acc[k] * scale;
+                if k != 0 && k != half - 1 {
+                    p *= 2.;
+                }
+                Complex::new(p, 0.)
+            })
+            .collect();
+
+        Spectrum::new_real(psd, signal.sample_rate / l)
+    }
 }
 
 
@@ -75,4 +230,51 @@ mod tests {
                                               Complex::new(1., 0.)], 4));
     }
 
+    #[test]
+    fn test_fft_ifft_round_trip() {
+        let v = Signal::from_reals(vec![1., 2., 3., 4.], 4);
+        let mut ft = ForwardFFT::new(4);
+        let mut ift = InverseFFT::new(4);
+        let roundtrip = ift.process(&ft.process(&v));
+        for (a, b) in roundtrip.to_vec().iter().zip(v.to_vec().iter()) {
+            assert!((a - b).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_process_windowed() {
+        let v = Signal::from_reals(vec![1., 1., 1., 1.], 4);
+        let mut ft = ForwardFFT::new(4);
+        let windowed = ft.process_windowed(&v, Window::Rectangular);
+        let plain = ft.process(&v);
+        assert_eq!(windowed, plain);
+    }
+
+    #[test]
+    fn test_process_real_length() {
+        let v = Signal::from_reals(vec![1., 0., 0., 0.], 4);
+        let mut ft = ForwardFFT::new(4);
+        let s = ft.process_real(&v);
+        assert_eq!(s.to_vec().len(), 3);
+    }
+
+    #[test]
+    fn test_process_real_round_trip() {
+        let v = Signal::from_reals(vec![1., 2., 3., 4.], 4);
+        let mut ft = ForwardFFT::new(4);
+        let mut ift = InverseFFT::new(4);
+        let roundtrip = ift.process_real(&ft.process_real(&v));
+        for (a, b) in roundtrip.to_vec().iter().zip(v.to_vec().iter()) {
+            assert!((a - b).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_welch_psd_length() {
+        let v = Signal::from_reals(vec![1.; 16], 16);
+        let periodogram = Periodogram::new(8);
+        let s = periodogram.welch_psd(&v);
+        assert_eq!(s.to_vec().len(), 5);
+    }
+
 }
\ No newline at end of file
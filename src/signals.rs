@@ -1,6 +1,8 @@
 //! Process Discrete signals in time domain
 
 
+use std::f64::consts::PI;
+
 use rand;
 use rand::distributions::{Normal, IndependentSample};
 use num_complex::{Complex, Complex64};
@@ -10,22 +12,22 @@ use vectors::{Vector, VectorImpl};
 #[derive(Debug, PartialEq)]
 pub struct Signal {
     data: Vector,
-    sample_freq: usize
+    pub sample_rate: usize
 }
 
 
 impl Signal {
 
-    /// Create new signal from vector
+    /// Create new signal from vector, with sample_rate defaulted to the vector length
     pub fn new(data: Vec<Complex64>) -> Signal {
         let n = data.len();
-        Signal { data: data, sample_freq: n }
+        Signal { data: data, sample_rate: n }
     }
 
-    /// Create new signal from vector of real numbers
-    pub fn from_reals(data: Vec<f64>) -> Signal {
+    /// Create new signal from vector of real numbers, sampled at the given rate
+    pub fn from_reals(data: Vec<f64>, sample_rate: usize) -> Signal {
         Signal { data: data.iter().map(|x| Complex::new(*x, 0.)).collect(),
-                 sample_freq: data.len()}
+                 sample_rate: sample_rate }
     }
 
     /// Signal length()
@@ -101,9 +103,282 @@ impl Signal {
         let normal = Normal::new(0.0, std);
         let mut rng = rand::thread_rng();
         let data = self.data.iter().map(|x| x + normal.ind_sample(&mut rng)).collect();
-        Signal { data: data, sample_freq: self.sample_freq }
+        Signal { data: data, sample_rate: self.sample_rate }
+    }
+
+    /// Apply a window function before spectral analysis
+    pub fn apply_window(&self, window: Window) -> Signal {
+        let taps = window.taps(self.data.len());
+        let data = self.data.iter().zip(taps.iter()).map(|(&x, &w)| x * w).collect();
+        Signal { data: data, sample_rate: self.sample_rate }
+    }
+
+    /// Linear convolution with another signal
+    /// y[n] = Sum x[k]*h[n-k] For all k
+    pub fn convolve(&self, other: &Signal) -> Signal {
+        let len = self.data.len() + other.data.len() - 1;
+        let mut v: Vec<Complex64> = Vec::with_capacity(len);
+        for n in 0..len as isize {
+            let mut acc = Complex::new(0., 0.);
+            for k in 0..self.data.len() as isize {
+                acc = acc + self.get(k) * other.get(n - k);
+            }
+            v.push(acc);
+        }
+        Signal::new(v)
+    }
+
+}
+
+/// Taper applied to a signal before transformation, to reduce spectral leakage
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Window {
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl Window {
+    /// Generate this window's coefficients for a signal of the given length
+    pub fn taps(&self, size: usize) -> Vec<f64> {
+        if size <= 1 {
+            return vec![1.; size];
+        }
+        let d = (size - 1) as f64;
+        match *self {
+            Window::Rectangular => vec![1.; size],
+            Window::Hann => (0..size).map(|n| 0.5 - 0.5 * (2. * PI * n as f64 / d).cos()).collect(),
+            Window::Hamming => (0..size).map(|n| 0.54 - 0.46 * (2. * PI * n as f64 / d).cos()).collect(),
+            Window::Blackman => (0..size).map(|n| {
+                let x = n as f64;
+                0.42 - 0.5 * (2. * PI * x / d).cos() + 0.08 * (4. * PI * x / d).cos()
+            }).collect(),
+        }
+    }
+}
+
+/// Finite Impulse Response filter
+/// Filters a signal by linear convolution with a fixed set of taps:
+/// y[n] = Sum h[k]*x[n-k] For all k
+/// `state` holds the trailing input history from the previous call to
+/// `process`, so consecutive calls filter as if run over one continuous
+/// signal rather than zero-padding at every block boundary.
+#[derive(Debug, PartialEq)]
+pub struct FirFilter {
+    taps: Vec<Complex64>,
+    state: Vec<Complex64>,
+}
+
+impl FirFilter {
+
+    /// Create a new filter from explicit taps
+    pub fn new(taps: Vec<Complex64>) -> FirFilter {
+        let history = taps.len().saturating_sub(1);
+        let state = vec![Complex::new(0., 0.); history];
+        FirFilter { taps: taps, state: state }
+    }
+
+    /// Apply the filter to a signal, producing an output of the same length
+    /// and carrying the trailing input history over to the next call
+    pub fn process(&mut self, signal: &Signal) -> Signal {
+        let history = self.state.clone();
+        let get = |n: isize| -> Complex64 {
+            if n < 0 {
+                Complex::new(0., 0.)
+            } else if (n as usize) < history.len() {
+                history[n as usize]
+            } else {
+                signal.get(n - history.len() as isize)
+            }
+        };
+
+        let mut v: Vec<Complex64> = Vec::with_capacity(signal.len());
+        for n in 0..signal.len() as isize {
+            let mut acc = Complex::new(0., 0.);
+            for k in 0..self.taps.len() as isize {
+                acc = acc + self.taps[k as usize] * get(n + history.len() as isize - k);
+            }
+            v.push(acc);
+        }
+
+        let state_len = self.state.len();
+        if state_len > 0 {
+            let total_len = history.len() + signal.len();
+            self.state = (0..state_len)
+                .map(|i| get(total_len as isize - state_len as isize + i as isize))
+                .collect();
+        }
+
+        Signal::new(v)
+    }
+
+    /// Design a lowpass filter using the windowed-sinc method
+    /// ## Params:
+    ///   * num_taps - Number of taps (filter length)
+    ///   * cutoff - Normalized cutoff frequency (0.0 .. 0.5, fraction of sample rate)
+    pub fn lowpass(num_taps: usize, cutoff: f64) -> FirFilter {
+        FirFilter::new(windowed_sinc_taps(num_taps, cutoff))
+    }
+
+    /// Design a highpass filter by spectral inversion of a lowpass design
+    pub fn highpass(num_taps: usize, cutoff: f64) -> FirFilter {
+        let mut taps = windowed_sinc_taps(num_taps, cutoff);
+        for t in taps.iter_mut() {
+            *t = -*t;
+        }
+        let mid = (num_taps - 1) / 2;
+        taps[mid] = taps[mid] + Complex::new(1., 0.);
+        FirFilter::new(taps)
+    }
+
+    /// Design a bandpass filter by combining two lowpass designs
+    pub fn bandpass(num_taps: usize, low_cutoff: f64, high_cutoff: f64) -> FirFilter {
+        let hi = windowed_sinc_taps(num_taps, high_cutoff);
+        let lo = windowed_sinc_taps(num_taps, low_cutoff);
+        let taps = hi.iter().zip(lo.iter()).map(|(&h, &l)| h - l).collect();
+        FirFilter::new(taps)
+    }
+
+}
+
+/// Second-order IIR filter section (biquad), in Direct Form II Transposed:
+/// y[n] = b0*x[n] + z0
+/// z0 = b1*x[n] - a1*y[n] + z1
+/// z1 = b2*x[n] - a2*y[n]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Biquad {
+    b: [f64; 3],
+    a: [f64; 3],
+    z: [Complex64; 2],
+}
+
+impl Biquad {
+
+    /// Construct from coefficients already normalized so that a0 == 1
+    pub fn new(b: [f64; 3], a: [f64; 3]) -> Biquad {
+        Biquad { b: b, a: a, z: [Complex::new(0., 0.); 2] }
+    }
+
+    /// Filter a signal, carrying delay state across calls
+    pub fn process(&mut self, signal: &Signal) -> Signal {
+        let mut v: Vec<Complex64> = Vec::with_capacity(signal.len());
+        let mut z0 = self.z[0];
+        let mut z1 = self.z[1];
+        for n in 0..signal.len() as isize {
+            let x = signal.get(n);
+            let y = x * self.b[0] + z0;
+            z0 = x * self.b[1] - y * self.a[1] + z1;
+            z1 = x * self.b[2] - y * self.a[2];
+            v.push(y);
+        }
+        self.z = [z0, z1];
+        Signal::new(v)
+    }
+
+    /// RBJ cookbook lowpass
+    pub fn lowpass(sample_rate: f64, f0: f64, q: f64) -> Biquad {
+        let c = RbjCoeffs::new(sample_rate, f0, q);
+        let b = [(1. - c.cos_w0) / 2., 1. - c.cos_w0, (1. - c.cos_w0) / 2.];
+        let a = [1. + c.alpha, -2. * c.cos_w0, 1. - c.alpha];
+        Biquad::normalized(b, a)
+    }
+
+    /// RBJ cookbook highpass
+    pub fn highpass(sample_rate: f64, f0: f64, q: f64) -> Biquad {
+        let c = RbjCoeffs::new(sample_rate, f0, q);
+        let b = [(1. + c.cos_w0) / 2., -(1. + c.cos_w0), (1. + c.cos_w0) / 2.];
+        let a = [1. + c.alpha, -2. * c.cos_w0, 1. - c.alpha];
+        Biquad::normalized(b, a)
+    }
+
+    /// RBJ cookbook constant skirt gain bandpass
+    pub fn bandpass(sample_rate: f64, f0: f64, q: f64) -> Biquad {
+        let c = RbjCoeffs::new(sample_rate, f0, q);
+        let b = [c.sin_w0 / 2., 0., -c.sin_w0 / 2.];
+        let a = [1. + c.alpha, -2. * c.cos_w0, 1. - c.alpha];
+        Biquad::normalized(b, a)
+    }
+
+    /// RBJ cookbook notch
+    pub fn notch(sample_rate: f64, f0: f64, q: f64) -> Biquad {
+        let c = RbjCoeffs::new(sample_rate, f0, q);
+        let b = [1., -2. * c.cos_w0, 1.];
+        let a = [1. + c.alpha, -2. * c.cos_w0, 1. - c.alpha];
+        Biquad::normalized(b, a)
+    }
+
+    /// Normalize b and a coefficients by a0
+    fn normalized(b: [f64; 3], a: [f64; 3]) -> Biquad {
+        let a0 = a[0];
+        Biquad::new([b[0] / a0, b[1] / a0, b[2] / a0], [1., a[1] / a0, a[2] / a0])
+    }
+
+}
+
+/// Intermediate terms shared by the RBJ audio-EQ cookbook designers
+struct RbjCoeffs {
+    cos_w0: f64,
+    sin_w0: f64,
+    alpha: f64,
+}
+
+impl RbjCoeffs {
+    fn new(sample_rate: f64, f0: f64, q: f64) -> RbjCoeffs {
+        let w0 = 2. * PI * f0 / sample_rate;
+        let cos_w0 = w0.cos();
+        let sin_w0 = w0.sin();
+        let alpha = sin_w0 / (2. * q);
+        RbjCoeffs { cos_w0: cos_w0, sin_w0: sin_w0, alpha: alpha }
+    }
+}
+
+/// A cascade of biquad sections, used to build higher-order filters
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cascade {
+    stages: Vec<Biquad>,
+}
+
+impl Cascade {
+
+    pub fn new(stages: Vec<Biquad>) -> Cascade {
+        Cascade { stages: stages }
+    }
+
+    /// Filter a signal through each stage in series
+    pub fn process(&mut self, signal: &Signal) -> Signal {
+        let mut out = signal.to_vec();
+        for stage in self.stages.iter_mut() {
+            out = stage.process(&Signal::new(out)).to_vec();
+        }
+        Signal::new(out)
+    }
+
+}
+
+/// sinc(x) = sin(pi*x) / (pi*x), with sinc(0) = 1
+fn sinc(x: f64) -> f64 {
+    if x == 0. {
+        1.
+    } else {
+        (PI * x).sin() / (PI * x)
     }
+}
 
+/// Windowed-sinc impulse response for a lowpass filter, normalized so taps sum to 1
+fn windowed_sinc_taps(num_taps: usize, cutoff: f64) -> Vec<Complex64> {
+    let m = (num_taps - 1) as f64 / 2.;
+    let mut taps: Vec<Complex64> = Vec::with_capacity(num_taps);
+    for n in 0..num_taps {
+        let ideal = 2. * cutoff * sinc(2. * cutoff * (n as f64 - m));
+        let window = 0.54 - 0.46 * (2. * PI * n as f64 / (num_taps - 1) as f64).cos();
+        taps.push(Complex::new(ideal * window, 0.));
+    }
+    let sum: Complex64 = taps.iter().fold(Complex::new(0., 0.), |acc, &t| acc + t);
+    for t in taps.iter_mut() {
+        *t = *t / sum;
+    }
+    taps
 }
 
 
@@ -132,7 +407,7 @@ mod tests {
 
     #[test]
     fn test_shift2() {
-        let v = Signal::from_reals(vec![1., 2., 3., 4.]);
+        let v = Signal::from_reals(vec![1., 2., 3., 4.], 4);
         let v1 = v.shift(-1);
         assert!(v1 == Signal::new(vec![Complex::new(2., 0.),
                                        Complex::new(3., 0.),
@@ -187,4 +462,101 @@ mod tests {
         assert!(v.power() == 14./4.);
     }
 
+    #[test]
+    fn test_apply_window_hann_tapers_edges_to_zero() {
+        let v = Signal::from_reals(vec![1., 1., 1., 1., 1.], 5);
+        let w = v.apply_window(Window::Hann);
+        assert!(w.get(0).re.abs() < 1e-9);
+        assert!(w.get(4).re.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_apply_window_rectangular_is_identity() {
+        let v = Signal::from_reals(vec![1., 2., 3.], 3);
+        let w = v.apply_window(Window::Rectangular);
+        assert!(w == v);
+    }
+
+    #[test]
+    fn test_window_taps_empty_does_not_panic() {
+        assert_eq!(Window::Hann.taps(0).len(), 0);
+    }
+
+    #[test]
+    fn test_window_taps_single_sample_is_not_nan() {
+        let taps = Window::Hann.taps(1);
+        assert!(!taps[0].is_nan());
+    }
+
+    #[test]
+    fn test_convolve() {
+        let x = Signal::from_reals(vec![1., 2., 3.], 3);
+        let h = Signal::from_reals(vec![0., 1.], 2);
+        let y = x.convolve(&h);
+        assert!(y == Signal::new(vec![Complex::new(0., 0.),
+                                      Complex::new(1., 0.),
+                                      Complex::new(2., 0.),
+                                      Complex::new(3., 0.)]));
+    }
+
+    #[test]
+    fn test_fir_filter_process() {
+        let mut filter = FirFilter::new(vec![Complex::new(1., 0.), Complex::new(1., 0.)]);
+        let x = Signal::from_reals(vec![1., 2., 3., 4.], 4);
+        let y = filter.process(&x);
+        assert!(y == Signal::new(vec![Complex::new(1., 0.),
+                                      Complex::new(3., 0.),
+                                      Complex::new(5., 0.),
+                                      Complex::new(7., 0.)]));
+    }
+
+    #[test]
+    fn test_fir_filter_streams_state_across_calls() {
+        let taps = vec![Complex::new(1., 0.), Complex::new(1., 0.), Complex::new(1., 0.)];
+        let mut whole = FirFilter::new(taps.clone());
+        let x = Signal::from_reals(vec![1., 2., 3., 4., 5., 6.], 6);
+        let expected = whole.process(&x);
+
+        let mut chunked = FirFilter::new(taps);
+        let first = chunked.process(&Signal::from_reals(vec![1., 2., 3.], 3));
+        let second = chunked.process(&Signal::from_reals(vec![4., 5., 6.], 3));
+        let actual = Signal::new([first.to_vec(), second.to_vec()].concat());
+
+        assert!(actual == expected);
+    }
+
+    #[test]
+    fn test_fir_lowpass_taps_sum_to_one() {
+        let filter = FirFilter::lowpass(15, 0.1);
+        let sum: Complex64 = filter.taps.iter().fold(Complex::new(0., 0.), |acc, &t| acc + t);
+        assert!((sum.re - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_biquad_lowpass_passes_dc() {
+        let mut filter = Biquad::lowpass(8000., 200., 0.707);
+        let x = Signal::from_reals(vec![1.; 256], 256);
+        let y = filter.process(&x);
+        assert!((y.get(255).re - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_cascade_matches_individual_stages() {
+        let x = Signal::from_reals(vec![1., 0., 0., 0., 0., 0., 0., 0.], 8);
+        let mut a = Biquad::lowpass(8000., 1000., 0.707);
+        let mut b = Biquad::lowpass(8000., 1000., 0.707);
+        let mut cascade = Cascade::new(vec![a.clone(), b.clone()]);
+
+        let expected = b.process(&a.process(&x));
+        let actual = cascade.process(&x);
+        assert!(actual == expected);
+    }
+
+    #[test]
+    fn test_fir_highpass_taps_sum_to_zero() {
+        let filter = FirFilter::highpass(15, 0.1);
+        let sum: Complex64 = filter.taps.iter().fold(Complex::new(0., 0.), |acc, &t| acc + t);
+        assert!(sum.re.abs() < 1e-9);
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,66 @@
+//! Frequency-domain representation produced by the FFT analyzers
+
+use num_complex::Complex64;
+
+/// Discrete Fourier spectrum: a set of complex bins paired with the sample
+/// rate used to produce them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectrum {
+    data: Vec<Complex64>,
+    pub sample_rate: usize,
+    one_sided: bool
+}
+
+impl Spectrum {
+
+    /// Create a new (full, two-sided) spectrum
+    pub fn new(data: Vec<Complex64>, sample_rate: usize) -> Spectrum {
+        Spectrum { data: data, sample_rate: sample_rate, one_sided: false }
+    }
+
+    /// Create a one-sided spectrum, e.g. the N/2+1 unique bins produced by
+    /// transforming a real-valued signal. Downstream magnitude/PSD code
+    /// should check `is_one_sided` before assuming a full, symmetric bin set.
+    pub fn new_real(data: Vec<Complex64>, sample_rate: usize) -> Spectrum {
+        Spectrum { data: data, sample_rate: sample_rate, one_sided: true }
+    }
+
+    /// Whether this spectrum holds only the unique bins of a real signal's
+    /// Hermitian-symmetric transform, rather than the full two-sided set
+    pub fn is_one_sided(&self) -> bool {
+        self.one_sided
+    }
+
+    /// Spectrum length (number of bins)
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Copy bins into a new vector
+    pub fn to_vec(&self) -> Vec<Complex64> {
+        self.data.clone()
+    }
+
+}
+
+
+/// ------------------------------------------------------------------------------------------------
+/// Module unit tests
+/// ------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use num_complex::{Complex};
+    use super::*;
+
+    #[test]
+    fn test_new_is_not_one_sided() {
+        let s = Spectrum::new(vec![Complex::new(1., 0.)], 4);
+        assert!(!s.is_one_sided());
+    }
+
+    #[test]
+    fn test_new_real_is_one_sided() {
+        let s = Spectrum::new_real(vec![Complex::new(1., 0.)], 4);
+        assert!(s.is_one_sided());
+    }
+}